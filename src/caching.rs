@@ -0,0 +1,65 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::SystemTime;
+
+use hyper::{Body, Request, Response, StatusCode};
+
+// Computes a strong ETag for `content` -- cheap enough to run per cache miss, and
+// sensitive to any byte-level change in the representation we're about to send.
+pub fn compute_etag(content: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// Computes a cheap ETag from a file's metadata alone -- used on the streaming path,
+// where hashing the full content would defeat the point of not buffering it in memory.
+pub fn compute_etag_from_metadata(mtime: SystemTime, size: u64) -> String {
+    let mut hasher = DefaultHasher::new();
+    mtime.hash(&mut hasher);
+    size.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// Whether the request's conditional headers say the client's cached copy is still
+// fresh. `Cache-Control: no-cache` on the request forces revalidation (we never
+// short-circuit in that case), and `If-None-Match` takes precedence over
+// `If-Modified-Since` per RFC 7232.
+pub fn is_not_modified(req: &Request<Body>, etag: &str, mtime: SystemTime) -> bool {
+    if let Some(value) = req.headers().get(hyper::header::CACHE_CONTROL).and_then(|v| v.to_str().ok()) {
+        if let Some(directives) = cache_control::CacheControl::from_value(value) {
+            if directives.cachability == Some(cache_control::Cachability::NoCache) {
+                return false;
+            }
+        }
+    }
+
+    if let Some(value) = req.headers().get(hyper::header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        return value.split(',').any(|tag| {
+            let tag = tag.trim();
+            tag == "*" || tag == etag
+        });
+    }
+
+    if let Some(value) = req.headers().get(hyper::header::IF_MODIFIED_SINCE).and_then(|v| v.to_str().ok()) {
+        if let Ok(since) = httpdate::parse_http_date(value) {
+            // HTTP dates only carry whole seconds (as does the `Last-Modified` we send),
+            // so compare at that precision rather than against the sub-second mtime.
+            let secs = |t: SystemTime| t.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            return secs(mtime) <= secs(since);
+        }
+    }
+
+    false
+}
+
+// Builds a bare `304 Not Modified` response carrying only the cache-validation headers.
+pub fn not_modified_response(etag: &str, mtime: SystemTime, cache_control: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header("etag", etag)
+        .header("last-modified", httpdate::fmt_http_date(mtime))
+        .header("cache-control", cache_control)
+        .body(Body::empty())
+        .unwrap()
+}
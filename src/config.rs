@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Parser;
+use serde::Deserialize;
+
+// On-disk configuration (TOML by default, or JSON if the file ends in `.json`),
+// with every field overridable from the CLI. The three per-type minify toggles
+// have no flag each; the single `--minify` flag sets all of them together.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub bind_addr: String,
+    pub https_addr: String,
+    pub tls_cert_path: String,
+    pub tls_key_path: String,
+    // Request paths resolve under this directory instead of the raw CWD-relative path
+    pub document_root: PathBuf,
+    pub brotli_quality: u8,
+    pub brotli_window_size: u8,
+    pub brotli_block_size: u8,
+    pub gzip_enabled: bool,
+    pub minify_html: bool,
+    pub minify_css: bool,
+    pub minify_js: bool,
+    // Total bytes the in-memory processed-asset cache is allowed to hold before we
+    // drop it and start fresh. TODO: replace with real LRU eviction.
+    pub cache_size_limit_bytes: u64,
+    // Sent verbatim as the `Cache-Control` response header on every served asset
+    pub cache_control: String,
+    // Whether a directory lacking an `index.html` gets an auto-generated listing,
+    // rather than a 404
+    pub directory_listing_enabled: bool,
+    // Files at or above this size bypass the in-memory cache and are streamed
+    // straight from disk instead, bounding per-request memory use
+    pub stream_threshold_bytes: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_addr: String::from("0.0.0.0:1337"),
+            https_addr: String::from("0.0.0.0:1443"),
+            tls_cert_path: String::from("cert.pem"),
+            tls_key_path: String::from("key.pem"),
+            document_root: PathBuf::from("."),
+            brotli_quality: 11,
+            brotli_window_size: 24,
+            brotli_block_size: 24,
+            gzip_enabled: true,
+            minify_html: true,
+            minify_css: true,
+            minify_js: true,
+            cache_size_limit_bytes: 256 * 1024 * 1024,
+            cache_control: String::from("public, max-age=3600"),
+            directory_listing_enabled: false,
+            stream_threshold_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl Config {
+    pub fn is_minifiable(&self, mime: &str) -> bool {
+        (self.minify_html && mime.starts_with("text/html"))
+            || (self.minify_css && mime.starts_with("text/css"))
+            || (self.minify_js && mime.starts_with("text/javascript"))
+    }
+
+    fn from_file(path: &PathBuf) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+            _ => toml::from_str(&contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn apply_cli(&mut self, cli: &Cli) {
+        if let Some(v) = &cli.bind_addr { self.bind_addr = v.clone(); }
+        if let Some(v) = &cli.https_addr { self.https_addr = v.clone(); }
+        if let Some(v) = &cli.tls_cert_path { self.tls_cert_path = v.clone(); }
+        if let Some(v) = &cli.tls_key_path { self.tls_key_path = v.clone(); }
+        if let Some(v) = &cli.document_root { self.document_root = v.clone(); }
+        if let Some(v) = cli.brotli_quality { self.brotli_quality = v; }
+        if let Some(v) = cli.brotli_window_size { self.brotli_window_size = v; }
+        if let Some(v) = cli.brotli_block_size { self.brotli_block_size = v; }
+        if let Some(v) = cli.gzip { self.gzip_enabled = v; }
+        if let Some(v) = cli.minify { self.minify_html = v; self.minify_css = v; self.minify_js = v; }
+        if let Some(v) = cli.cache_size_limit_bytes { self.cache_size_limit_bytes = v; }
+        if let Some(v) = &cli.cache_control { self.cache_control = v.clone(); }
+        if let Some(v) = cli.directory_listing { self.directory_listing_enabled = v; }
+        if let Some(v) = cli.stream_threshold_bytes { self.stream_threshold_bytes = v; }
+    }
+
+    // Loads the config file named by `--config` (if any), then layers any
+    // explicitly-passed CLI flags on top of it.
+    pub fn load() -> Self {
+        let cli = Cli::parse();
+
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path).unwrap_or_else(|e| {
+                eprintln!("Couldn't read config file '{}', falling back to defaults: {}", path.display(), e);
+                Self::default()
+            }),
+            None => Self::default(),
+        };
+
+        config.apply_cli(&cli);
+        config
+    }
+}
+
+// CLI flags, each optional so a config-file value is only overridden when actually passed.
+#[derive(Debug, Parser)]
+#[command(name = "cee-de-en", about = "A tiny, compression-aware static asset server")]
+struct Cli {
+    /// Path to a TOML (or JSON) config file
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Bind address for the plain HTTP listener, e.g. 0.0.0.0:1337
+    #[arg(long)]
+    bind_addr: Option<String>,
+
+    /// Bind address for the HTTPS listener, e.g. 0.0.0.0:1443
+    #[arg(long)]
+    https_addr: Option<String>,
+
+    /// Path to the TLS certificate chain (PEM)
+    #[arg(long)]
+    tls_cert_path: Option<String>,
+
+    /// Path to the TLS private key (PEM)
+    #[arg(long)]
+    tls_key_path: Option<String>,
+
+    /// Directory that request paths resolve under
+    #[arg(long)]
+    document_root: Option<PathBuf>,
+
+    /// Brotli quality, 0-11
+    #[arg(long)]
+    brotli_quality: Option<u8>,
+
+    /// Brotli window size, 10-24
+    #[arg(long)]
+    brotli_window_size: Option<u8>,
+
+    /// Brotli block size, 16-24
+    #[arg(long)]
+    brotli_block_size: Option<u8>,
+
+    /// Offer gzip as a fallback encoding alongside Brotli
+    #[arg(long)]
+    gzip: Option<bool>,
+
+    /// Minify HTML/CSS/JS before compressing
+    #[arg(long)]
+    minify: Option<bool>,
+
+    /// Maximum total bytes held by the in-memory processed-asset cache
+    #[arg(long)]
+    cache_size_limit_bytes: Option<u64>,
+
+    /// Value sent as the `Cache-Control` header on every served asset
+    #[arg(long)]
+    cache_control: Option<String>,
+
+    /// Auto-generate a directory listing when a directory has no `index.html`
+    #[arg(long)]
+    directory_listing: Option<bool>,
+
+    /// Files at or above this size (bytes) are streamed from disk instead of cached
+    #[arg(long)]
+    stream_threshold_bytes: Option<u64>,
+}
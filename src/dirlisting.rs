@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Entry {
+    name: String,
+    size: u64,
+    is_dir: bool,
+}
+
+// Reads `dir`'s entries into a sorted, servable list. Entries we can't stat are
+// silently skipped rather than failing the whole listing.
+pub fn list_entries(dir: &Path) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = fs::read_dir(dir)
+        .map(|read_dir| {
+            read_dir
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    Some(Entry {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        size: metadata.len(),
+                        is_dir: metadata.is_dir(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+// Escapes text for safe interpolation into HTML -- entry names come straight
+// from the filesystem, so a file called e.g. `<script>` must not get its
+// markup injected verbatim into every visitor's directory listing.
+fn escape_html(raw: &str) -> String {
+    raw.chars().fold(String::with_capacity(raw.len()), |mut escaped, c| {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+        escaped
+    })
+}
+
+// Percent-encodes a single path segment for use in an `href` -- entry names come
+// straight from the filesystem, so a name containing e.g. `?`, `#`, or a space
+// would otherwise be parsed as a query string/fragment or truncated by the browser.
+fn percent_encode_path_segment(raw: &str) -> String {
+    raw.bytes().fold(String::with_capacity(raw.len()), |mut encoded, b| {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char);
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+        encoded
+    })
+}
+
+// Renders a minimal HTML directory index.
+pub fn render_html(label: &str, entries: &[Entry]) -> String {
+    let label = escape_html(label);
+    let mut html = format!(
+        "<!doctype html><html><head><title>Index of /{label}</title></head><body><h1>Index of /{label}</h1><ul>"
+    );
+
+    for entry in entries {
+        let href = percent_encode_path_segment(&entry.name);
+        let name = escape_html(&entry.name);
+        let suffix = if entry.is_dir { "/" } else { "" };
+        html.push_str(&format!(
+            "<li><a href=\"{0}{1}\">{2}{1}</a> ({3} bytes)</li>",
+            href, suffix, name, entry.size
+        ));
+    }
+
+    html.push_str("</ul></body></html>");
+    html
+}
+
+// Renders the same listing as a JSON array of `{name, size, is_dir}`.
+pub fn render_json(entries: &[Entry]) -> String {
+    serde_json::to_string(entries).unwrap_or_else(|_| "[]".to_string())
+}
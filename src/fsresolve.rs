@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+// Outcome of resolving a request path under the document root.
+#[derive(Debug)]
+pub enum Resolved {
+    File(PathBuf),
+    Directory(PathBuf),
+    // Canonicalized, but escapes the document root (e.g. via `../..`)
+    Forbidden,
+    NotFound,
+}
+
+// Resolves `path_string` under `document_root`, canonicalizing both sides and
+// rejecting anything that escapes the root -- this is what stops `..`-style
+// path traversal from reaching files outside the served directory.
+pub fn resolve(document_root: &Path, path_string: &str) -> Resolved {
+    let root_canon = match document_root.canonicalize() {
+        Ok(root) => root,
+        Err(_) => return Resolved::NotFound,
+    };
+
+    let candidate = document_root.join(path_string);
+    let canon = match candidate.canonicalize() {
+        Ok(canon) => canon,
+        Err(_) => return Resolved::NotFound,
+    };
+
+    if !canon.starts_with(&root_canon) {
+        return Resolved::Forbidden;
+    }
+
+    if canon.is_dir() {
+        Resolved::Directory(canon)
+    } else {
+        Resolved::File(canon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Builds a scratch document root with a file and a subdirectory; torn down
+    // by the `Drop` impl when the guard goes out of scope.
+    struct TestRoot(PathBuf);
+
+    impl TestRoot {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("cee-de-en-fsresolve-test-{name}"));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(root.join("subdir")).unwrap();
+            fs::write(root.join("file.txt"), b"hello").unwrap();
+            TestRoot(root)
+        }
+    }
+
+    impl Drop for TestRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn resolves_a_plain_file() {
+        let root = TestRoot::new("file");
+        assert!(matches!(resolve(&root.0, "file.txt"), Resolved::File(_)));
+    }
+
+    #[test]
+    fn resolves_a_subdirectory() {
+        let root = TestRoot::new("dir");
+        assert!(matches!(resolve(&root.0, "subdir"), Resolved::Directory(_)));
+    }
+
+    #[test]
+    fn rejects_traversal_above_the_root() {
+        let root = TestRoot::new("traversal");
+        assert!(matches!(resolve(&root.0, "../"), Resolved::Forbidden));
+        assert!(matches!(resolve(&root.0, "subdir/../../"), Resolved::Forbidden));
+    }
+
+    #[test]
+    fn reports_missing_paths_as_not_found() {
+        let root = TestRoot::new("missing");
+        assert!(matches!(resolve(&root.0, "does-not-exist.txt"), Resolved::NotFound));
+    }
+}
@@ -1,32 +1,196 @@
 use std::fs;
+use std::future::Future;
 use std::io::Write;
-use std::path::PathBuf;
+use std::pin::Pin;
 use std::net::SocketAddr;
 use std::convert::Infallible;
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::time::SystemTime;
 
 use hyper::{Server, Request, Response, Body, Method, StatusCode};
-use hyper::service::{service_fn, make_service_fn};
-
-use minify_html;
+use hyper::server::conn::Http;
+use hyper::service::{service_fn, make_service_fn, Service};
 
 use brotlic::{BlockSize, BrotliEncoderOptions, CompressorWriter, Quality, WindowSize};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+mod caching;
+mod config;
+mod dirlisting;
+mod fsresolve;
+mod mime;
+mod range;
+mod streaming;
+mod tls;
+
+use config::Config;
+
+// Every request path ultimately resolves to one of these boxed futures, whether
+// it was served from the in-memory cache or streamed straight off disk -- this
+// lets a single service type be reused for both the plain HTTP and TLS listeners.
+type ResponseFuture = Pin<Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>>;
+
+// The content-codings we know how to produce, in our own preference order
+// (used as the tie-breaker when a client weights several equally via `q=`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Encoding {
+    Brotli,
+    Gzip,
+    Identity,
+}
+
+impl Encoding {
+    // The `Content-Encoding` value to send, or `None` for identity (which omits the header).
+    fn content_encoding_header(&self) -> Option<&'static str> {
+        match self {
+            Encoding::Brotli => Some("br"),
+            Encoding::Gzip => Some("gzip"),
+            Encoding::Identity => None,
+        }
+    }
+
+    fn preference_rank(&self) -> u8 {
+        match self {
+            Encoding::Brotli => 0,
+            Encoding::Gzip => 1,
+            Encoding::Identity => 2,
+        }
+    }
+}
+
+// Parses an `Accept-Encoding` header into the codings we support, each paired with
+// its effective `q` value, sorted best-first (highest `q`, then our own preference).
+// `identity` is implicitly acceptable at `q=1.0` unless the client says otherwise,
+// per RFC 7231 -- and a bare `*` fills in any coding not already mentioned by name.
+fn parse_accept_encoding(header: &str) -> Vec<(Encoding, f32)> {
+    let mut explicit: HashMap<Encoding, f32> = HashMap::new();
+    let mut wildcard_q: Option<f32> = None;
+    let mut identity_explicit = false;
+
+    for part in header.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        let mut pieces = part.split(';');
+        let token = pieces.next().unwrap_or("").trim();
+        let mut q: f32 = 1.0;
+        for param in pieces {
+            if let Some(value) = param.trim().strip_prefix("q=") {
+                q = value.trim().parse().unwrap_or(1.0);
+            }
+        }
+
+        // Named codings are recorded even at `q=0`, so a later `*` can't re-enable
+        // them; anything at `q=0` is dropped once the wildcard has been applied.
+        match token {
+            "*" => wildcard_q = Some(q),
+            "br" => { explicit.insert(Encoding::Brotli, q); },
+            "gzip" => { explicit.insert(Encoding::Gzip, q); },
+            "identity" => {
+                identity_explicit = true;
+                explicit.insert(Encoding::Identity, q);
+            },
+            _ => {} // unsupported coding (e.g. `deflate`, `zstd`), we don't speak it
+        }
+    }
+
+    if !identity_explicit {
+        match wildcard_q {
+            Some(q) if q > 0.0 => { explicit.insert(Encoding::Identity, q); },
+            Some(_) => {}, // wildcard explicitly disabled, and identity wasn't named
+            None => { explicit.insert(Encoding::Identity, 1.0); },
+        }
+    }
+
+    if let Some(q) = wildcard_q {
+        if q > 0.0 {
+            explicit.entry(Encoding::Brotli).or_insert(q);
+            explicit.entry(Encoding::Gzip).or_insert(q);
+        }
+    }
+
+    let mut candidates: Vec<(Encoding, f32)> = explicit.into_iter().filter(|(_, q)| *q > 0.0).collect();
+    candidates.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap()
+            .then_with(|| a.0.preference_rank().cmp(&b.0.preference_rank()))
+    });
+    candidates
+}
+
+// Picks the best mutually-supported encoding for a request, falling back to
+// `identity` when no header is present or nothing offered is supported.
+fn negotiate_encoding(header: Option<&str>) -> Encoding {
+    match header {
+        None => Encoding::Identity,
+        Some(h) => parse_accept_encoding(h)
+            .into_iter()
+            .map(|(enc, _)| enc)
+            .next()
+            .unwrap_or(Encoding::Identity),
+    }
+}
 
 // Utility function for serving content via it's byte form
 async fn serve_content(
     req: Request<Body>,
     content: Arc<Vec<u8>>,
+    encoding: Encoding,
+    mime: String,
+    etag: String,
+    mtime: SystemTime,
+    cache_control: String,
 ) -> Result<Response<Body>, Infallible> {
     match req.method() {
         // Serve the content for every GET request
-        &Method::GET => Ok(
-            Response::builder()
+        &Method::GET => {
+            // Ranges are only meaningful against the identity representation -- a byte
+            // offset into a Brotli/gzip stream doesn't correspond to anything on the
+            // decoded side -- so callers are expected to have already forced identity
+            // encoding whenever a Range header is present.
+            if encoding == Encoding::Identity {
+                match range::parse_range(&req, content.len() as u64) {
+                    Some(Err(())) => return Ok(range::not_satisfiable_response(content.len() as u64)),
+                    Some(Ok(byte_range)) => {
+                        let total = content.len() as u64;
+                        let slice = content[byte_range.start as usize..=byte_range.end as usize].to_vec();
+                        return Ok(Response::builder()
+                            .status(StatusCode::PARTIAL_CONTENT)
+                            .header("content-type", mime)
+                            .header("vary", "Accept-Encoding")
+                            .header("accept-ranges", "bytes")
+                            .header("etag", etag)
+                            .header("last-modified", httpdate::fmt_http_date(mtime))
+                            .header("cache-control", cache_control)
+                            .header("content-range", format!("bytes {}-{}/{}", byte_range.start, byte_range.end, total))
+                            .body(hyper::Body::from(slice))
+                            .unwrap());
+                    },
+                    None => {},
+                }
+            }
+
+            let mut builder = Response::builder()
                 .status(StatusCode::OK)
-                .header("content-encoding", "br")
-                .header("content-type", "text/javascript") // TODO: automate the content-type filling
-                .body(hyper::Body::from((*content).clone())).unwrap()
-        ),
+                .header("content-type", mime)
+                .header("vary", "Accept-Encoding")
+                .header("accept-ranges", "bytes")
+                .header("etag", etag)
+                .header("last-modified", httpdate::fmt_http_date(mtime))
+                .header("cache-control", cache_control);
+
+            if let Some(header_value) = encoding.content_encoding_header() {
+                builder = builder.header("content-encoding", header_value);
+            }
+
+            Ok(builder.body(hyper::Body::from((*content).clone())).unwrap())
+        },
 
         // All other routes are 404s
         _ => Ok(
@@ -38,96 +202,358 @@ async fn serve_content(
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), std::io::Error> {
-    // TODO: read settings from a config file, Serde ftw
-    // Note: maybe we want to accept some basic CLI input too?
+// Bare 404, used both when a path doesn't resolve to anything on disk and when
+// a resolved directory has no index and listings are disabled.
+fn not_found_response() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body("No such resource".into())
+        .unwrap()
+}
 
-    // TODO: read address and port from config
-    let selected_addr = String::from("0.0.0.0:1337");
-    let addr = selected_addr.parse::<SocketAddr>().unwrap();
+// Bare 403, used when a request path canonicalizes to somewhere outside the
+// document root (i.e. `fsresolve::resolve` caught a traversal attempt).
+fn forbidden_response() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .body("Forbidden".into())
+        .unwrap()
+}
 
-    // Build the Hyper server
-    let svc_builder = make_service_fn(move |_conn| {
-        // Create our hit caches
-        let mut processed_cache: HashMap<String, Arc<Vec<u8>>> = HashMap::new();
-        async {
-            // Create our 'CDN' endpoint which essentially just assumes the request path to be a *relative* disk path
-            Ok::<_, Infallible>(
-                service_fn(move |req: Request<Body>| {
-                    println!("Serving resource: {}", req.uri());
-
-                    // Parse the resource path (chopping off the initial `/`)
-                    let path_string = &req.uri().path().to_string()[1..];
-                    let path = PathBuf::from(&path_string);
-                    let path_key = path.clone().to_string_lossy().to_string();
-
-                    // Read from resource path (TODO: better handling of missing files, 404s, etc)
-                    let file_contents: Vec<u8>;
-                    let cache_hit = processed_cache.contains_key(&path_key);
-                    
-                    // Return from hit cache with zero pre-processing (extreme speed), or load from disk and process on-demand (slow)
-                    if cache_hit {
-                        file_contents = (*processed_cache.get(&path_key).unwrap()).to_vec();
-                    } else {
-                        file_contents = fs::read(path.clone()).unwrap_or(format!("Nope, no {path_string} found here m8").into_bytes());
-                    }
-
-                    // All operations in the non-cache block are for uncached resource only
-                    if cache_hit {
-                        println!("Resource served instantly from cache!");
-                    } else {
-                        // First: Minify! (if applicable)
-                        let mut minified: Vec<u8> = Vec::new();
-
-                        // TODO: move to MIME-type comparisons, not strings?
-                        if path_string.ends_with(".html") || path_string.ends_with(".js") || path_string.ends_with(".css") {
-                            // HTML minify (TODO: improve JS minifying with a dedicated lib or custom function, also add comment removal somehow)
-                            let mut cfg = minify_html::Cfg::new();
-                            cfg.keep_comments = false;
-                            minified = minify_html::minify(&file_contents, &cfg);
-                        }
-
-                        // Second: Brotli compression!
-                        // TODO: move the encoder options outside of the service scope (pre-load) and load config values set by the user, if set.
-                        let encoder = BrotliEncoderOptions::new()
-                            .quality(Quality::best())
-                            .window_size(WindowSize::best())
-                            .block_size(BlockSize::best())
-                            .build().unwrap();
-                        let mut compressed_writer = CompressorWriter::with_encoder(encoder, Vec::new());
-
-                        // TODO: catch any weird compression errors and fallback to raw file (why would these happen?)
-                        let compressed_file: Vec<u8>;
-                        if minified.len() > 0 {
-                            compressed_writer.write_all(minified.as_slice()).unwrap();
-                            compressed_file = compressed_writer.into_inner().unwrap();
-                            println!("Resource was [{}] bytes, reduced to [{}] via minifying and then [{}] by Brotli", file_contents.len(), minified.len(), compressed_file.len());
-                        } else {
-                            compressed_writer.write_all(file_contents.as_slice()).unwrap();
-                            compressed_file = compressed_writer.into_inner().unwrap();
-                            println!("Resource was [{}] bytes, reduced to [{}] by Brotli", minified.len(), compressed_file.len());
-                        }
-
-                        // Capture the return bytes in an Arc so we can use the reference repeatedly
-                        // across async tasks that the server will spawn, then push the Arc in to our
-                        // cache memory.
-                        processed_cache.insert(path_key, Arc::new(compressed_file));
-                    }
-
-                    // Serve the compressed bytes!
-                    serve_content(req, processed_cache.get(&path.to_string_lossy().to_string()).unwrap().to_owned())
-                })
-            )
+// Renders a directory's contents as HTML or JSON depending on what the client asked for.
+fn directory_listing_response(req: &Request<Body>, label: &str, dir: &std::path::Path) -> Response<Body> {
+    let wants_json = req
+        .headers()
+        .get(hyper::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("application/json"))
+        .unwrap_or(false);
+
+    let entries = dirlisting::list_entries(dir);
+    if wants_json {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "application/json")
+            .body(dirlisting::render_json(&entries).into())
+            .unwrap()
+    } else {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/html")
+            .body(dirlisting::render_html(label, &entries).into())
+            .unwrap()
+    }
+}
+
+// Builds one instance of our 'CDN' service -- essentially just assumes the request
+// path to be a *relative* disk path -- with its own private hit caches. Shared by
+// both the plain HTTP and TLS listeners so either can serve the same content.
+fn build_service(config: Arc<Config>, brotli_options: Arc<BrotliEncoderOptions>) -> impl Service<Request<Body>, Response = Response<Body>, Error = Infallible, Future = ResponseFuture> + Send {
+    // Create our hit caches, now keyed per-encoding so we can serve whichever
+    // representation a client actually asked for without recompressing.
+    let mut processed_cache: HashMap<(String, Encoding), Arc<Vec<u8>>> = HashMap::new();
+    // Resolved MIME types don't vary by encoding, so they get their own cache
+    let mut mime_cache: HashMap<String, String> = HashMap::new();
+    // Each representation gets its own ETag, but modification time is per-resource
+    let mut etag_cache: HashMap<(String, Encoding), String> = HashMap::new();
+    let mut mtime_cache: HashMap<String, SystemTime> = HashMap::new();
+    // On-disk file size per path, so deciding between the cache and streaming paths
+    // doesn't have to stat the file again on every cache hit
+    let mut size_cache: HashMap<String, u64> = HashMap::new();
+    // Running total of bytes held by `processed_cache`, checked against the configured cap
+    let mut cache_bytes: u64 = 0;
+
+    service_fn(move |req: Request<Body>| -> ResponseFuture {
+        println!("Serving resource: {}", req.uri());
+
+        // Everything below only applies to GET; let serve_content's
+        // existing dispatch handle any other method with a 404.
+        if req.method() != Method::GET {
+            return Box::pin(serve_content(req, Arc::new(Vec::new()), Encoding::Identity, String::new(), String::new(), SystemTime::UNIX_EPOCH, String::new()));
+        }
+
+        // Parse the resource path (chopping off the initial `/`) and resolve it under the
+        // document root -- canonicalizing both sides so `..`-style traversal can't escape it.
+        let path_string = &req.uri().path().to_string()[1..];
+        let (path, path_key) = match fsresolve::resolve(&config.document_root, path_string) {
+            fsresolve::Resolved::Forbidden => return Box::pin(async move { Ok(forbidden_response()) }),
+            fsresolve::Resolved::NotFound => return Box::pin(async move { Ok(not_found_response()) }),
+            fsresolve::Resolved::Directory(dir) => {
+                let index = dir.join("index.html");
+                if index.is_file() {
+                    let key = format!("{}/index.html", path_string.trim_end_matches('/'));
+                    (index, key)
+                } else if config.directory_listing_enabled {
+                    let label = path_string.to_string();
+                    return Box::pin(async move { Ok(directory_listing_response(&req, &label, &dir)) });
+                } else {
+                    return Box::pin(async move { Ok(not_found_response()) });
+                }
+            },
+            fsresolve::Resolved::File(file) => (file, path_string.to_string()),
+        };
+
+        // Work out which encoding this client can actually accept
+        let accept_encoding = req
+            .headers()
+            .get(hyper::header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let mut encoding = negotiate_encoding(accept_encoding.as_deref());
+        if encoding == Encoding::Gzip && !config.gzip_enabled {
+            encoding = Encoding::Identity;
+        }
+
+        // A compressed byte stream can't be sliced into a meaningful range, so a
+        // Range request always gets the identity representation regardless of
+        // what Accept-Encoding negotiated.
+        if req.headers().contains_key(hyper::header::RANGE) {
+            encoding = Encoding::Identity;
         }
+
+        // Large files bypass the cache entirely and stream straight off
+        // disk so a single request can't balloon server memory or block
+        // the async task on a big synchronous compression pass. The size is
+        // cached per path so repeat requests (including cache hits below)
+        // don't pay for a stat call every time.
+        let file_size = match size_cache.get(&path_key) {
+            Some(&size) => size,
+            None => {
+                let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                size_cache.insert(path_key.clone(), size);
+                size
+            }
+        };
+        if file_size >= config.stream_threshold_bytes {
+            let mime = mime::resolve_by_extension(&path_key)
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            return Box::pin(streaming::serve_streamed(req, path, encoding, mime, file_size, config.cache_control.clone()));
+        }
+
+        let cache_key = (path_key.clone(), encoding);
+        let cache_hit = processed_cache.contains_key(&cache_key);
+
+        // Return from hit cache with zero pre-processing (extreme speed), or load from disk and process on-demand (slow)
+        if cache_hit {
+            println!("Resource served instantly from cache!");
+        } else {
+            // fsresolve already confirmed this path existed; a miss here only happens if
+            // it was removed in the narrow race between that check and this read.
+            let file_contents: Vec<u8> = fs::read(&path).unwrap_or(format!("Nope, no {path_key} found here m8").into_bytes());
+
+            // Capture the file's modification time for `Last-Modified`/`If-Modified-Since`
+            let mtime = fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::now());
+            mtime_cache.insert(path_key.clone(), mtime);
+
+            // Resolve and cache the MIME type before we touch the bytes any further
+            let resolved_mime = mime::resolve(&path_key, &file_contents);
+            mime_cache.insert(path_key.clone(), resolved_mime.clone());
+
+            // First: Minify! (if applicable and enabled for this type)
+            let mut minified: Vec<u8> = Vec::new();
+
+            if config.is_minifiable(&resolved_mime) {
+                // HTML minify (TODO: improve JS minifying with a dedicated lib or custom function, also add comment removal somehow)
+                let mut cfg = minify_html::Cfg::new();
+                cfg.keep_comments = false;
+                minified = minify_html::minify(&file_contents, &cfg);
+            }
+
+            let identity_bytes = if !minified.is_empty() { minified } else { file_contents };
+
+            // Second: Brotli compression. `BrotliEncoder` carries live FFI state and isn't
+            // `Clone`, so a fresh one is built from the shared, cheaply-`Clone`-able
+            // options on every cache miss rather than trying to reuse a single instance.
+            let mut compressed_writer = CompressorWriter::with_encoder(brotli_options.build().unwrap(), Vec::new());
+
+            // TODO: catch any weird compression errors and fallback to raw file (why would these happen?)
+            compressed_writer.write_all(identity_bytes.as_slice()).unwrap();
+            let brotli_bytes = compressed_writer.into_inner().unwrap();
+
+            // Third: Gzip compression, for clients that don't speak Brotli (when enabled)
+            let gzip_bytes = if config.gzip_enabled {
+                let mut gzip_writer = GzEncoder::new(Vec::new(), Compression::best());
+                gzip_writer.write_all(identity_bytes.as_slice()).unwrap();
+                gzip_writer.finish().unwrap()
+            } else {
+                Vec::new()
+            };
+
+            println!(
+                "Resource was [{}] bytes, reduced to [{}] by Brotli and [{}] by gzip",
+                identity_bytes.len(), brotli_bytes.len(), gzip_bytes.len()
+            );
+
+            // If the cache has grown past its configured cap, drop it and start fresh
+            // rather than letting it grow unbounded. TODO: real LRU eviction.
+            let incoming_bytes = (brotli_bytes.len() + gzip_bytes.len() + identity_bytes.len()) as u64;
+            if cache_bytes + incoming_bytes > config.cache_size_limit_bytes {
+                println!("Cache cap reached ({} bytes), clearing processed cache", config.cache_size_limit_bytes);
+                processed_cache.clear();
+                mime_cache.clear();
+                etag_cache.clear();
+                mtime_cache.clear();
+                size_cache.clear();
+                cache_bytes = 0;
+                // The clear above also dropped this resource's own freshly-captured entries
+                mtime_cache.insert(path_key.clone(), mtime);
+                mime_cache.insert(path_key.clone(), resolved_mime);
+            }
+
+            // Capture the return bytes in an Arc so we can use the reference repeatedly
+            // across async tasks that the server will spawn, then push each representation
+            // we're able to serve into our cache memory, alongside its own ETag.
+            etag_cache.insert((path_key.clone(), Encoding::Brotli), caching::compute_etag(&brotli_bytes));
+            processed_cache.insert((path_key.clone(), Encoding::Brotli), Arc::new(brotli_bytes));
+            if config.gzip_enabled {
+                etag_cache.insert((path_key.clone(), Encoding::Gzip), caching::compute_etag(&gzip_bytes));
+                processed_cache.insert((path_key.clone(), Encoding::Gzip), Arc::new(gzip_bytes));
+            }
+            etag_cache.insert((path_key.clone(), Encoding::Identity), caching::compute_etag(&identity_bytes));
+            processed_cache.insert((path_key.clone(), Encoding::Identity), Arc::new(identity_bytes));
+            cache_bytes += incoming_bytes;
+        }
+
+        // Serve a 304 if the client's cached copy is still fresh, otherwise the full body
+        let etag = etag_cache.get(&cache_key).unwrap().clone();
+        let mtime = *mtime_cache.get(&path_key).unwrap();
+        if caching::is_not_modified(&req, &etag, mtime) {
+            let cache_control = config.cache_control.clone();
+            return Box::pin(async move { Ok(caching::not_modified_response(&etag, mtime, &cache_control)) });
+        }
+
+        let mime = mime_cache.get(&path_key).unwrap().clone();
+        Box::pin(serve_content(req, processed_cache.get(&cache_key).unwrap().to_owned(), encoding, mime, etag, mtime, config.cache_control.clone()))
+    })
+}
+
+// Runs the plain HTTP listener. Never returns under normal operation.
+async fn run_http(addr: SocketAddr, config: Arc<Config>, brotli_options: Arc<BrotliEncoderOptions>) -> Result<(), std::io::Error> {
+    let svc_builder = make_service_fn(move |_conn| {
+        let svc = build_service(config.clone(), brotli_options.clone());
+        async move { Ok::<_, Infallible>(svc) }
     });
 
-    // Start up our service and accept connections
-    println!("Starting server at interface '{}'...", selected_addr);
+    println!("Starting HTTP server at interface '{}'...", addr);
     let server = Server::bind(&addr).serve(svc_builder);
     if let Err(e) = server.await {
-        eprintln!("Server error: {}", e);
+        eprintln!("HTTP server error: {}", e);
+    }
+
+    Ok(())
+}
+
+// Runs the HTTPS listener: terminates TLS with rustls and hands the decrypted
+// stream to Hyper, which negotiates HTTP/2 or HTTP/1.1 per the ALPN outcome.
+async fn run_https(addr: SocketAddr, acceptor: TlsAcceptor, config: Arc<Config>, brotli_options: Arc<BrotliEncoderOptions>) -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind(addr).await?;
+
+    println!("Starting HTTPS server at interface '{}'...", addr);
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => { eprintln!("HTTPS accept error: {}", e); continue; },
+        };
+        let acceptor = acceptor.clone();
+        let config = config.clone();
+        let brotli_options = brotli_options.clone();
+
+        tokio::spawn(async move {
+            let tls_stream = match acceptor.accept(stream).await {
+                Ok(tls_stream) => tls_stream,
+                Err(e) => { eprintln!("TLS handshake error: {}", e); return; },
+            };
+
+            // ALPN tells us whether the client negotiated HTTP/2
+            let negotiated_h2 = tls_stream
+                .get_ref()
+                .1
+                .alpn_protocol()
+                .map(|proto| proto == b"h2")
+                .unwrap_or(false);
+
+            let result = Http::new()
+                .http2_only(negotiated_h2)
+                .serve_connection(tls_stream, build_service(config, brotli_options))
+                .await;
+
+            if let Err(e) = result {
+                eprintln!("HTTPS connection error: {}", e);
+            }
+        });
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), std::io::Error> {
+    let config = Arc::new(Config::load());
+
+    let http_addr = config.bind_addr.parse::<SocketAddr>().unwrap();
+    let https_addr = config.https_addr.parse::<SocketAddr>().unwrap();
+
+    // Build the Brotli encoder *options* once at startup rather than re-parsing config
+    // on every cache miss; a fresh `BrotliEncoder` is then built from these per-request,
+    // since the encoder itself carries live state and can't be shared or cloned.
+    let brotli_options = Arc::new(
+        BrotliEncoderOptions::new()
+            .quality(Quality::new(config.brotli_quality).unwrap_or_else(|_| Quality::best()))
+            .window_size(WindowSize::new(config.brotli_window_size).unwrap_or_else(|_| WindowSize::best()))
+            .block_size(BlockSize::new(config.brotli_block_size).unwrap_or_else(|_| BlockSize::best()))
+            .clone()
+    );
+
+    // Run HTTP and HTTPS side-by-side; a missing cert/key simply disables HTTPS
+    // rather than taking the whole server down, since plain HTTP still works.
+    let http = run_http(http_addr, config.clone(), brotli_options.clone());
+    match tls::build_acceptor(&config.tls_cert_path, &config.tls_key_path) {
+        Ok(acceptor) => {
+            let https = run_https(https_addr, acceptor, config.clone(), brotli_options.clone());
+            let (http_result, https_result) = tokio::join!(http, https);
+            http_result?;
+            https_result?;
+        },
+        Err(e) => {
+            eprintln!("HTTPS disabled, couldn't load TLS cert/key ({}): {}", config.tls_cert_path, e);
+            http.await?;
+        },
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::*;
+
+    #[test]
+    fn no_header_means_identity() {
+        assert_eq!(negotiate_encoding(None), Encoding::Identity);
+    }
+
+    #[test]
+    fn prefers_brotli_over_gzip_at_equal_q() {
+        assert_eq!(negotiate_encoding(Some("gzip, br")), Encoding::Brotli);
+    }
+
+    #[test]
+    fn highest_q_value_wins_over_our_preference_order() {
+        assert_eq!(negotiate_encoding(Some("br;q=0.1, gzip;q=0.9, identity;q=0")), Encoding::Gzip);
+    }
+
+    #[test]
+    fn wildcard_fills_in_unlisted_codings() {
+        assert_eq!(negotiate_encoding(Some("*;q=0.5")), Encoding::Brotli);
+    }
+
+    #[test]
+    fn explicit_identity_q0_without_alternatives_still_yields_identity() {
+        // No br/gzip on offer, so identity remains the only usable choice even
+        // though the client marked it unacceptable -- there's nothing else to serve.
+        assert_eq!(negotiate_encoding(Some("identity;q=0")), Encoding::Identity);
+    }
+
+    #[test]
+    fn unsupported_codings_are_ignored() {
+        assert_eq!(negotiate_encoding(Some("deflate, zstd")), Encoding::Identity);
+    }
+}
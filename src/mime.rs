@@ -0,0 +1,50 @@
+use content_inspector::{inspect, ContentType};
+
+// Canonical (extension, mime) table, checked before any sniffing is attempted.
+const EXTENSION_TABLE: &[(&str, &str)] = &[
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("json", "application/json"),
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("wasm", "application/wasm"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("xml", "application/xml"),
+    ("pdf", "application/pdf"),
+];
+
+// Looks up the canonical MIME type for `path`'s extension, if we know it.
+pub fn resolve_by_extension(path: &str) -> Option<String> {
+    let extension = path.rsplit('.').next().filter(|ext| *ext != path)?;
+    let ext_lower = extension.to_lowercase();
+    EXTENSION_TABLE
+        .iter()
+        .find(|(known, _)| *known == ext_lower)
+        .map(|(_, mime)| mime.to_string())
+}
+
+// Resolves a MIME type for `path`: first by extension via the table above, and
+// for unknown or extension-less paths, by sniffing the first bytes of `content`.
+pub fn resolve(path: &str, content: &[u8]) -> String {
+    if let Some(mime) = resolve_by_extension(path) {
+        return mime;
+    }
+
+    // Unknown extension (or none at all): sniff the bytes to tell text from binary
+    match inspect(content) {
+        ContentType::BINARY => "application/octet-stream".to_string(),
+        _ => "text/plain; charset=utf-8".to_string(),
+    }
+}
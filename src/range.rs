@@ -0,0 +1,123 @@
+use hyper::{Body, Request, Response, StatusCode};
+
+// A single, already-validated byte range (inclusive on both ends) into a
+// resource of known total length. We only support one range per request --
+// the `multipart/byteranges` form isn't implemented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+// Parses a `Range: bytes=...` header against a resource of `total` bytes, handling
+// the `start-end`, `start-`, and `-suffixlen` forms from RFC 7233. Returns `None`
+// when there's no (usable) Range header -- the caller should then serve the full
+// body -- or `Some(Err(()))` when one was present but unsatisfiable, in which case
+// the caller should send `416` instead of ignoring it.
+pub fn parse_range(req: &Request<Body>, total: u64) -> Option<Result<ByteRange, ()>> {
+    let value = req.headers().get(hyper::header::RANGE)?.to_str().ok()?;
+    let spec = value.strip_prefix("bytes=")?;
+
+    // A list of ranges asks for `multipart/byteranges`, which we don't produce;
+    // fall back to serving the full body rather than reject the request outright.
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start_str, end_str) = spec.split_once('-')?;
+
+    let range = if start_str.is_empty() {
+        // `-suffixlen`: the last `suffixlen` bytes of the resource
+        let suffix_len: u64 = match end_str.parse() {
+            Ok(n) => n,
+            Err(_) => return Some(Err(())),
+        };
+        if suffix_len == 0 || total == 0 {
+            return Some(Err(()));
+        }
+        ByteRange { start: total.saturating_sub(suffix_len), end: total - 1 }
+    } else {
+        let start: u64 = match start_str.parse() {
+            Ok(n) => n,
+            Err(_) => return Some(Err(())),
+        };
+        let end = if end_str.is_empty() {
+            total.saturating_sub(1)
+        } else {
+            match end_str.parse::<u64>() {
+                Ok(end) => end.min(total.saturating_sub(1)),
+                Err(_) => return Some(Err(())),
+            }
+        };
+        ByteRange { start, end }
+    };
+
+    if total == 0 || range.start > range.end || range.start >= total {
+        return Some(Err(()));
+    }
+
+    Some(Ok(range))
+}
+
+// Builds the bare `416 Range Not Satisfiable` response for an unsatisfiable range.
+pub fn not_satisfiable_response(total: u64) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .header("content-range", format!("bytes */{total}"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_with_range(value: &str) -> Request<Body> {
+        Request::builder().header(hyper::header::RANGE, value).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn no_range_header_returns_none() {
+        let req = Request::builder().body(Body::empty()).unwrap();
+        assert!(parse_range(&req, 100).is_none());
+    }
+
+    #[test]
+    fn parses_start_end_form() {
+        let req = request_with_range("bytes=0-49");
+        let range = parse_range(&req, 100).unwrap().unwrap();
+        assert_eq!((range.start, range.end), (0, 49));
+    }
+
+    #[test]
+    fn parses_open_ended_form() {
+        let req = request_with_range("bytes=50-");
+        let range = parse_range(&req, 100).unwrap().unwrap();
+        assert_eq!((range.start, range.end), (50, 99));
+    }
+
+    #[test]
+    fn parses_suffix_length_form() {
+        let req = request_with_range("bytes=-10");
+        let range = parse_range(&req, 100).unwrap().unwrap();
+        assert_eq!((range.start, range.end), (90, 99));
+    }
+
+    #[test]
+    fn rejects_range_starting_past_the_end() {
+        let req = request_with_range("bytes=500-600");
+        assert_eq!(parse_range(&req, 100), Some(Err(())));
+    }
+
+    #[test]
+    fn multiple_ranges_fall_back_to_the_full_body() {
+        let req = request_with_range("bytes=0-10,20-30");
+        assert!(parse_range(&req, 100).is_none());
+    }
+}
@@ -0,0 +1,96 @@
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder};
+use hyper::{Body, Request, Response, StatusCode};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, BufReader};
+use tokio_util::io::ReaderStream;
+
+use crate::caching;
+use crate::range;
+use crate::Encoding;
+
+// Serves `path` as a chunked, bounded-memory stream, compressing on the fly
+// when the negotiated encoding calls for it. Never touches the in-memory cache.
+// Assumes the caller has already confirmed the request method is GET, and has
+// already forced `encoding` to `Identity` whenever `req` carries a Range header.
+// The size threshold for routing a request here at all lives in `Config::stream_threshold_bytes`.
+pub async fn serve_streamed(
+    req: Request<Body>,
+    path: PathBuf,
+    encoding: Encoding,
+    mime: String,
+    total: u64,
+    cache_control: String,
+) -> Result<Response<Body>, Infallible> {
+    let mut file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(_) => {
+            return Ok(
+                Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body("No such resource".into())
+                    .unwrap()
+            );
+        }
+    };
+
+    // A cheap ETag derived from mtime+size -- hashing the full content here
+    // would defeat the point of streaming it straight off disk unbuffered.
+    let mtime = file.metadata().await.and_then(|m| m.modified()).unwrap_or_else(|_| SystemTime::now());
+    let etag = caching::compute_etag_from_metadata(mtime, total);
+
+    if caching::is_not_modified(&req, &etag, mtime) {
+        return Ok(caching::not_modified_response(&etag, mtime, &cache_control));
+    }
+
+    if encoding == Encoding::Identity {
+        match range::parse_range(&req, total) {
+            Some(Err(())) => return Ok(range::not_satisfiable_response(total)),
+            Some(Ok(byte_range)) => {
+                if file.seek(std::io::SeekFrom::Start(byte_range.start)).await.is_err() {
+                    return Ok(range::not_satisfiable_response(total));
+                }
+                let reader = BufReader::new(file).take(byte_range.len());
+                let body = Body::wrap_stream(ReaderStream::new(reader));
+
+                return Ok(Response::builder()
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header("content-type", mime)
+                    .header("vary", "Accept-Encoding")
+                    .header("accept-ranges", "bytes")
+                    .header("etag", etag)
+                    .header("last-modified", httpdate::fmt_http_date(mtime))
+                    .header("cache-control", cache_control)
+                    .header("content-range", format!("bytes {}-{}/{}", byte_range.start, byte_range.end, total))
+                    .body(body)
+                    .unwrap());
+            },
+            None => {},
+        }
+    }
+
+    let reader = BufReader::new(file);
+    let body = match encoding {
+        Encoding::Brotli => Body::wrap_stream(ReaderStream::new(BrotliEncoder::new(reader))),
+        Encoding::Gzip => Body::wrap_stream(ReaderStream::new(GzipEncoder::new(reader))),
+        Encoding::Identity => Body::wrap_stream(ReaderStream::new(reader)),
+    };
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", mime)
+        .header("vary", "Accept-Encoding")
+        .header("accept-ranges", "bytes")
+        .header("etag", etag)
+        .header("last-modified", httpdate::fmt_http_date(mtime))
+        .header("cache-control", cache_control);
+
+    if let Some(header_value) = encoding.content_encoding_header() {
+        builder = builder.header("content-encoding", header_value);
+    }
+
+    Ok(builder.body(body).unwrap())
+}
@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::{certs, pkcs8_private_keys};
+use tokio_rustls::TlsAcceptor;
+
+// Builds a TLS acceptor from a PEM certificate chain and private key, with no
+// client-auth requirement, advertising both `h2` and `http/1.1` over ALPN so
+// browsers negotiate HTTP/2 on our compressed-asset workload where they can.
+pub fn build_acceptor(cert_path: &str, key_path: &str) -> io::Result<TlsAcceptor> {
+    let cert_file = &mut BufReader::new(File::open(cert_path)?);
+    let key_file = &mut BufReader::new(File::open(key_path)?);
+
+    let cert_chain = certs(cert_file)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid certificate chain"))?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    let mut keys = pkcs8_private_keys(key_file)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid private key"))?;
+    if keys.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no private key found in key file"));
+    }
+    let key = PrivateKey(keys.remove(0));
+
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}